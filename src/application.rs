@@ -10,7 +10,7 @@ use winit::event_loop::ActiveEventLoop;
 use winit::window::{Window, WindowId};
 
 use crate::render::camera::{OrthographicProjection, PrimaryCamera};
-use crate::render::{RenderContext, Sprite};
+use crate::render::{RenderContext, Sprite, TonemapOperator};
 use crate::transform::Transform;
 
 pub struct VenturaApp {
@@ -47,7 +47,11 @@ impl ApplicationHandler for VenturaApp {
 		let window_builder = event_loop.create_window(window_attributes).expect("Failed to create window.");
 
 		let window = Arc::new(window_builder);
-		let render_context = RenderContext::new(window.clone());
+		let mut render_context = RenderContext::new(window.clone());
+
+		// Prefer the filmic Aces curve over the default Reinhard operator so
+		// additive/glow sprites roll off more gracefully once they exceed 1.0.
+		render_context.set_tonemap(TonemapOperator::Aces, 1.0);
 
 		self.primary_window = Some(window);
 		self.render_context = Some(render_context);
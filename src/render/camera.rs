@@ -13,11 +13,19 @@ pub struct CameraTarget;
 #[derive(Copy, Clone, Debug)]
 pub struct OrthographicProjection;
 
+/// Half-extent of the orthographic depth range, in world units. Sprite layers
+/// span `[-DEPTH_RANGE, DEPTH_RANGE]`; a [`Transform`](crate::transform::Transform)
+/// layer outside this interval is clipped by the near/far planes.
+pub const DEPTH_RANGE: f32 = 1000.0;
+
 impl OrthographicProjection {
 	pub fn matrix(&self, window_width: f32, window_height: f32) -> Mat4 {
 		let half_width = window_width / 2.0;
 		let half_height = window_height / 2.0;
-		Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, -1.0, 1.0)
+		// The near/far planes bracket the layer range used by `Transform::layer`
+		// so sorted sprites across the whole interval stay inside the depth clip
+		// volume instead of vanishing at `|layer| > 1`.
+		Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, -DEPTH_RANGE, DEPTH_RANGE)
 	}
 }
 
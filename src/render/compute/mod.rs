@@ -0,0 +1,201 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec2;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{BindGroupLayout, ComputePass, Device, Queue};
+
+use crate::render::sprite::SpriteInstanceData;
+use crate::render::util::StorageBinding;
+
+/// The WGSL shader used to integrate the particle simulation each frame.
+const PARTICLE_SHADER: wgpu::ShaderModuleDescriptor = wgpu::include_wgsl!("./shaders/particle.wgsl");
+
+/// Number of particles advanced by a single workgroup, matching the
+/// `@workgroup_size(64)` declared in the compute shader.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Per-particle state stored on the GPU.
+///
+/// The layout is shared between the compute shader that simulates particles and
+/// the vertex stage that renders them, so the same buffer feeds both without a
+/// CPU round-trip. `life` counts down in seconds; a particle is dead once it
+/// reaches zero.
+#[repr(C)]
+#[derive(Pod, Zeroable, Copy, Clone, Debug)]
+pub struct Particle {
+	pub position: Vec2,
+	pub velocity: Vec2,
+	pub color: [f32; 4],
+	pub life: f32,
+	_padding: [f32; 3],
+}
+
+impl Particle {
+	pub fn new(position: Vec2, velocity: Vec2, color: [f32; 4], life: f32) -> Self {
+		Self { position, velocity, color, life, _padding: [0.0; 3] }
+	}
+}
+
+/// Emitter configuration uploaded to the simulation each frame.
+///
+/// `delta_time` is the integration step the compute shader applies. `lifetime`,
+/// `spawn_rate` and `velocity_min`/`velocity_max` are uploaded alongside it but
+/// the particle shader does not read them: it spawns no new particles, so only
+/// the particles passed to [`ParticleSystem::seed`] are simulated, and each stays
+/// dead once its `life` runs out.
+#[repr(C)]
+#[derive(Pod, Zeroable, Copy, Clone, Debug)]
+pub struct EmitterParams {
+	pub delta_time: f32,
+	pub lifetime: f32,
+	pub spawn_rate: f32,
+	_padding: f32,
+	pub velocity_min: Vec2,
+	pub velocity_max: Vec2,
+}
+
+impl EmitterParams {
+	pub fn new(lifetime: f32, spawn_rate: f32, velocity_min: Vec2, velocity_max: Vec2) -> Self {
+		Self { delta_time: 0.0, lifetime, spawn_rate, _padding: 0.0, velocity_min, velocity_max }
+	}
+}
+
+/// GPU-driven particle simulation.
+///
+/// Particle state lives in a [`StorageBinding`] created with
+/// [`StorageBinding::create_compute`], so its buffer carries both `STORAGE` (for
+/// the compute shader) and `VERTEX` usage. Each frame the compute shader
+/// integrates the particles and writes a parallel [`SpriteInstanceData`] buffer
+/// — also `STORAGE | VERTEX` — which is bound directly as the instance buffer of
+/// the sprite pipeline, so particles reach the screen without a CPU round-trip.
+/// Call [`ParticleSystem::set_params`] to update the emitter, then
+/// [`ComputePipeline::dispatch`] to advance the simulation.
+pub struct ParticleSystem {
+	particles: StorageBinding<Particle>,
+	instances: StorageBinding<SpriteInstanceData>,
+	emitter_buffer: wgpu::Buffer,
+	emitter_bind_group_layout: BindGroupLayout,
+	emitter_bind_group: wgpu::BindGroup,
+	count: u32,
+}
+
+impl ParticleSystem {
+	pub fn new(device: &Device, capacity: u32, params: EmitterParams) -> Self {
+		let particles = StorageBinding::create_compute(device, Some("Particle Buffer".into()), capacity as u64);
+		let instances =
+			StorageBinding::create_compute(device, Some("Particle Instance Buffer".into()), capacity as u64);
+
+		let emitter_buffer = device.create_buffer_init(&BufferInitDescriptor {
+			label: Some("Emitter Uniform Buffer"),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			contents: bytemuck::bytes_of(&params),
+		});
+
+		let emitter_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: Some("Emitter Bind Group Layout"),
+			entries: &[wgpu::BindGroupLayoutEntry {
+				binding: 0,
+				visibility: wgpu::ShaderStages::COMPUTE,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			}],
+		});
+
+		let emitter_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some("Emitter Bind Group"),
+			layout: &emitter_bind_group_layout,
+			entries: &[wgpu::BindGroupEntry { binding: 0, resource: emitter_buffer.as_entire_binding() }],
+		});
+
+		Self { particles, instances, emitter_buffer, emitter_bind_group_layout, emitter_bind_group, count: capacity }
+	}
+
+	/// Seed the simulation with an initial batch of particles.
+	pub fn seed(&mut self, device: &Device, queue: &Queue, particles: &[Particle]) {
+		self.count = particles.len() as u32;
+		self.particles.write_slice(device, queue, particles);
+	}
+
+	/// Update the emitter parameters and this frame's integration step.
+	pub fn set_params(&self, queue: &Queue, mut params: EmitterParams, delta_time: f32) {
+		params.delta_time = delta_time;
+		queue.write_buffer(&self.emitter_buffer, 0, bytemuck::bytes_of(&params));
+	}
+
+	/// The storage buffer the compute shader writes each frame, ready to be bound
+	/// as the instance buffer of the sprite pipeline.
+	pub fn instance_buffer(&self) -> &wgpu::Buffer {
+		self.instances.buffer()
+	}
+
+	/// Number of particles advanced and rendered each frame.
+	pub fn count(&self) -> u32 {
+		self.count
+	}
+
+	/// Bind group layout for the emitter uniform, used to build the compute
+	/// pipeline layout.
+	pub fn emitter_bind_group_layout(&self) -> &BindGroupLayout {
+		&self.emitter_bind_group_layout
+	}
+}
+
+/// Compute pipeline that integrates the particle simulation on the GPU.
+///
+/// Mirrors [`SpritePipeline`](crate::render::sprite::SpritePipeline): it owns the
+/// [`wgpu::ComputePipeline`] and records its dispatch into a command encoder. The
+/// particle storage buffer is bound at group 0, the emitter uniform at group 1,
+/// and the sprite-instance output buffer at group 2.
+pub struct ComputePipeline {
+	pipeline: wgpu::ComputePipeline,
+}
+
+impl ComputePipeline {
+	pub fn new(device: &Device, particles: &ParticleSystem) -> Self {
+		let shader = device.create_shader_module(PARTICLE_SHADER);
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Particle Compute Pipeline Layout"),
+			bind_group_layouts: &[
+				particles.particles.bind_group_layout(),
+				particles.emitter_bind_group_layout(),
+				particles.instances.bind_group_layout(),
+			],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+			label: Some("Particle Compute Pipeline"),
+			layout: Some(&pipeline_layout),
+			module: &shader,
+			entry_point: Some("cs_main"),
+			compilation_options: Default::default(),
+			cache: None,
+		});
+
+		Self { pipeline }
+	}
+
+	/// Record a dispatch that advances every live particle by one frame.
+	pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, particles: &ParticleSystem) {
+		let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+			label: Some("Particle Compute Pass"),
+			timestamp_writes: None,
+		});
+
+		self.record(&mut compute_pass, particles);
+	}
+
+	fn record(&self, compute_pass: &mut ComputePass, particles: &ParticleSystem) {
+		let workgroups = particles.count.div_ceil(WORKGROUP_SIZE);
+
+		compute_pass.set_pipeline(&self.pipeline);
+		compute_pass.set_bind_group(0, particles.particles.bind_group(), &[]);
+		compute_pass.set_bind_group(1, &particles.emitter_bind_group, &[]);
+		compute_pass.set_bind_group(2, particles.instances.bind_group(), &[]);
+		compute_pass.dispatch_workgroups(workgroups.max(1), 1, 1);
+	}
+}
@@ -1,17 +1,35 @@
 use std::sync::Arc;
 
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec2, Vec3};
 use hecs::World;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{Device, Queue, Surface, SurfaceConfiguration, SurfaceError};
+use wgpu::{Device, Queue, Surface, SurfaceConfiguration, SurfaceError, TextureView};
 use winit::window::Window;
 
 use crate::render::camera::{CameraUniform, OrthographicProjection, PrimaryCamera};
+use crate::render::compute::{ComputePipeline, EmitterParams, Particle, ParticleSystem};
+use crate::render::graph::{RenderGraph, RenderGraphPass, PRESENT_SLOT};
+use crate::render::hdr::{HdrPipeline, TonemapOperator};
+use crate::render::pool::{TextureHandle, TexturePool};
 use crate::render::sprite::SpritePipeline;
+use crate::render::video::VideoTexture;
 use crate::transform::Transform;
 
-/// The color used to clear the surface each frame.
-const CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.01, g: 0.01, b: 0.01, a: 1.0 };
+/// The graph slot the sprite pass renders the HDR scene into, and that the
+/// tonemap pass samples.
+const SCENE_SLOT: crate::render::graph::SlotId = "scene";
+
+/// Number of GPU particles the render context simulates and draws each frame.
+const PARTICLE_CAPACITY: u32 = 256;
+
+/// Fixed integration step handed to the particle simulation. The engine does not
+/// yet thread a frame clock through `render`, so the simulation advances by a
+/// nominal 60 Hz tick each frame.
+const PARTICLE_TIMESTEP: f32 = 1.0 / 60.0;
+
+/// The multisample count requested for the sprite pass. The actual count is
+/// clamped to what the adapter supports for the HDR format.
+const DESIRED_SAMPLE_COUNT: u32 = 4;
 
 /// Provides a context for interacting with the WGPU API.
 ///
@@ -27,6 +45,11 @@ pub struct RenderContext {
 	camera_buffer: wgpu::Buffer,
 	camera_bind_group: wgpu::BindGroup,
 	sprite_pipeline: SpritePipeline,
+	texture_pool: TexturePool,
+	hdr: HdrPipeline,
+	graph: RenderGraph,
+	particles: ParticleSystem,
+	compute_pipeline: ComputePipeline,
 }
 
 impl RenderContext {
@@ -82,9 +105,72 @@ impl RenderContext {
 		// Create the uniform buffer and bind group for the camera.
 		let (camera_buffer, camera_bind_group_layout, camera_bind_group) = Self::create_camera_buffer(&device);
 
-		let sprite_pipeline = SpritePipeline::new(&device, &queue, &camera_bind_group_layout);
+		// Pick the highest supported multisample count up to the desired value.
+		let sample_count = Self::supported_sample_count(&adapter, DESIRED_SAMPLE_COUNT);
+		if sample_count < DESIRED_SAMPLE_COUNT {
+			tracing::warn!(
+				"Requested {}x MSAA for the sprite pass but the adapter supports at most {}x; falling back.",
+				DESIRED_SAMPLE_COUNT,
+				sample_count,
+			);
+		}
+
+		let texture_pool = TexturePool::new(&device, &queue);
+		let sprite_pipeline = SpritePipeline::new(
+			&device,
+			&camera_bind_group_layout,
+			texture_pool.bind_group_layout(),
+			&surface_configuration,
+			sample_count,
+		);
+
+		let hdr = HdrPipeline::new(&device, &surface_configuration);
+		let graph = RenderGraph::new(&device, &surface_configuration);
+
+		// Seed a radial burst so the compute simulation has live particles to
+		// advance and render from the first frame.
+		let mut particles = ParticleSystem::new(&device, PARTICLE_CAPACITY, Self::emitter_params());
+		particles.seed(&device, &queue, &Self::seed_particles(PARTICLE_CAPACITY));
+		let compute_pipeline = ComputePipeline::new(&device, &particles);
 
-		Self { surface, surface_configuration, device, queue, camera_buffer, camera_bind_group, sprite_pipeline }
+		Self {
+			surface,
+			surface_configuration,
+			device,
+			queue,
+			camera_buffer,
+			camera_bind_group,
+			sprite_pipeline,
+			texture_pool,
+			hdr,
+			graph,
+			particles,
+			compute_pipeline,
+		}
+	}
+
+	/// Emitter configuration for the particle simulation: a two-second lifetime
+	/// and a symmetric initial-velocity range. Used for the initial uniform when
+	/// the particle system is created and re-uploaded each frame; the seeded
+	/// particles set their own lifetime and velocity in `seed_particles`.
+	fn emitter_params() -> EmitterParams {
+		EmitterParams::new(2.0, 64.0, Vec2::splat(-120.0), Vec2::splat(120.0))
+	}
+
+	/// Build the initial particle burst: each particle starts at the origin with
+	/// a velocity fanned evenly around the circle and a color whose red rises and
+	/// green falls with the angle while blue follows its sine, so the seed is
+	/// deterministic and needs no RNG.
+	fn seed_particles(count: u32) -> Vec<Particle> {
+		(0..count)
+			.map(|index| {
+				let fraction = index as f32 / count as f32;
+				let angle = fraction * std::f32::consts::TAU;
+				let velocity = Vec2::new(angle.cos(), angle.sin()) * 80.0;
+				let color = [fraction, 1.0 - fraction, 0.5 + 0.5 * angle.sin(), 1.0];
+				Particle::new(Vec2::ZERO, velocity, color, 2.0)
+			})
+			.collect()
 	}
 
 	/// Render the current frame to the surface.
@@ -93,31 +179,163 @@ impl RenderContext {
 		let texture_view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
+		self.record_scene(world, &mut encoder, &texture_view);
+
+		self.queue.submit([encoder.finish()]);
+		output.present();
+		Ok(())
+	}
+
+	/// Render the scene into an owned offscreen texture and copy it back to the
+	/// CPU, returning the captured frame.
+	///
+	/// `copy_texture_to_buffer` requires each row to be padded up to
+	/// [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`], so the readback buffer is
+	/// allocated with a padded stride and the padding is stripped while building
+	/// the final image.
+	pub fn render_to_image(&mut self, world: &mut World) -> image::RgbaImage {
+		let width = self.surface_configuration.width;
+		let height = self.surface_configuration.height;
+		let format = self.surface_configuration.format;
+
+		let target = self.device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Screenshot Target"),
+			size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+			view_formats: &[],
+		});
+		let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+		let padded_bytes_per_row = ((4 * width + wgpu::COPY_BYTES_PER_ROW_ALIGNMENT - 1)
+			/ wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+			* wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+		let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Screenshot Readback Buffer"),
+			size: (padded_bytes_per_row * height) as u64,
+			usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+		self.record_scene(world, &mut encoder, &target_view);
+		encoder.copy_texture_to_buffer(
+			wgpu::TexelCopyTextureInfo {
+				texture: &target,
+				mip_level: 0,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+			},
+			wgpu::TexelCopyBufferInfo {
+				buffer: &readback_buffer,
+				layout: wgpu::TexelCopyBufferLayout {
+					offset: 0,
+					bytes_per_row: Some(padded_bytes_per_row),
+					rows_per_image: Some(height),
+				},
+			},
+			wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+		);
+		self.queue.submit([encoder.finish()]);
+
+		// Block until the copy completes and the buffer is mapped.
+		let slice = readback_buffer.slice(..);
+		slice.map_async(wgpu::MapMode::Read, |_| {});
+		self.device.poll(wgpu::Maintain::Wait);
+
+		// Only BGRA surfaces need the channel swap; an RGBA surface is copied
+		// straight through, otherwise the readback would be silently swizzled.
+		let swap_bgra = matches!(format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb);
+
+		let mapped = slice.get_mapped_range();
+		let pixels = Self::readback_to_rgba(&mapped, padded_bytes_per_row as usize, width, height, swap_bgra);
+
+		drop(mapped);
+		readback_buffer.unmap();
+
+		image::RgbaImage::from_raw(width, height, pixels).expect("Readback produced a mismatched pixel buffer.")
+	}
+
+	/// Strip the per-row copy padding from a mapped readback buffer into tightly
+	/// packed RGBA pixels, swapping byte order only when the source stored BGRA.
+	///
+	/// `copy_texture_to_buffer` pads each row up to
+	/// [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`], so each row carries
+	/// `padded_bytes_per_row` bytes of which only the leading `4 * width` are real
+	/// pixels.
+	fn readback_to_rgba(mapped: &[u8], padded_bytes_per_row: usize, width: u32, height: u32, swap_bgra: bool) -> Vec<u8> {
+		let unpadded_bytes_per_row = 4 * width as usize;
+		let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+		for row in mapped.chunks_exact(padded_bytes_per_row) {
+			for pixel in row[..unpadded_bytes_per_row].chunks_exact(4) {
+				if swap_bgra {
+					pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+				} else {
+					pixels.extend_from_slice(pixel);
+				}
+			}
+		}
+		pixels
+	}
+
+	/// Register a streaming [`VideoTexture`] with the texture pool and return a
+	/// handle a [`Sprite`](crate::render::Sprite) can reference. Frames pushed
+	/// into the video texture at a constant resolution update the shared binding
+	/// in place, so a sprite holding the handle samples the latest frame each
+	/// `prepare`; NV12 sources should also set the sprite's sample mode from
+	/// [`VideoTexture::sample_mode`].
+	pub fn register_video_texture(&mut self, video: &VideoTexture) -> TextureHandle {
+		self.texture_pool.register_binding(video.binding_arc())
+	}
+
+	/// Configure the tonemap operator and exposure scalar applied when the HDR
+	/// scene is compressed down to the surface's dynamic range.
+	pub fn set_tonemap(&mut self, operator: TonemapOperator, exposure: f32) {
+		self.hdr.set_tonemap(&self.queue, operator, exposure);
+	}
+
+	/// Record the scene through the render graph into `encoder`, writing the
+	/// tonemapped result onto `output`.
+	///
+	/// Two passes are scheduled: a scene pass that draws sprites into the HDR
+	/// [`SCENE_SLOT`] — so additive/glow sprites can exceed 1.0 — and a tonemap
+	/// pass that samples that slot and writes the compressed result into the
+	/// graph's [`PRESENT_SLOT`]. The graph's terminal present blits that slot onto
+	/// `output`.
+	fn record_scene(&mut self, world: &mut World, encoder: &mut wgpu::CommandEncoder, output: &TextureView) {
 		// Update the camera uniform buffer.
 		self.update_camera_buffer(world);
 
 		// Upload sprite information to the sprite pipeline.
-		self.sprite_pipeline.prepare(&self.queue, world);
-
-		{
-			let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-				label: Some("Render Pass"),
-				color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-					view: &texture_view,
-					resolve_target: None,
-					ops: wgpu::Operations { load: wgpu::LoadOp::Clear(CLEAR_COLOR), store: wgpu::StoreOp::Store },
-				})],
-				depth_stencil_attachment: None,
-				timestamp_writes: None,
-				occlusion_query_set: None,
-			});
-
-			self.sprite_pipeline.draw(&mut render_pass, &self.camera_bind_group);
-		}
+		self.sprite_pipeline.prepare(&self.device, &self.queue, &mut self.texture_pool, world);
 
-		self.queue.submit([encoder.finish()]);
-		output.present();
-		Ok(())
+		// Advance the particle simulation on the GPU; its output instance buffer
+		// is drawn alongside the sprites below, still resident on the GPU.
+		self.particles.set_params(&self.queue, Self::emitter_params(), PARTICLE_TIMESTEP);
+		self.compute_pipeline.dispatch(encoder, &self.particles);
+
+		// Bind disjoint fields into locals so the pass closures can borrow them
+		// while `self.graph` is borrowed mutably by `execute`.
+		let sprite_pipeline = &self.sprite_pipeline;
+		let camera_bind_group = &self.camera_bind_group;
+		let texture_pool = &self.texture_pool;
+		let hdr = &self.hdr;
+		let device = &self.device;
+		let particles = (self.particles.instance_buffer(), self.particles.count());
+
+		let passes = [
+			RenderGraphPass::new("scene", Vec::new(), SCENE_SLOT, move |encoder, target, _inputs| {
+				sprite_pipeline.draw(encoder, target, camera_bind_group, texture_pool, Some(particles));
+			}),
+			RenderGraphPass::new("tonemap", vec![SCENE_SLOT], PRESENT_SLOT, move |encoder, target, inputs| {
+				hdr.tonemap(device, encoder, inputs[0], target);
+			}),
+		];
+
+		self.graph.execute(device, &self.surface_configuration, encoder, output, &passes);
 	}
 
 	/// Resize the WGPU surface.
@@ -126,11 +344,23 @@ impl RenderContext {
 			self.surface_configuration.width = new_width;
 			self.surface_configuration.height = new_height;
 			self.surface.configure(&self.device, &self.surface_configuration);
+			self.graph.resize(&self.device, &self.surface_configuration);
+			self.sprite_pipeline.resize(&self.device, &self.surface_configuration);
 		} else {
 			tracing::warn!("Attempted to resize WGPU surface width or height smaller than zero.");
 		}
 	}
 
+	/// Returns the largest sample count no greater than `desired` that the
+	/// adapter supports for the HDR format, falling back to single-sampling.
+	fn supported_sample_count(adapter: &wgpu::Adapter, desired: u32) -> u32 {
+		let flags = adapter.get_texture_format_features(HdrPipeline::format()).flags;
+		[8, 4, 2, 1]
+			.into_iter()
+			.find(|&count| count <= desired && flags.sample_count_supported(count))
+			.unwrap_or(1)
+	}
+
 	fn create_camera_buffer(device: &Device) -> (wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup) {
 		let camera_buffer = device.create_buffer_init(&BufferInitDescriptor {
 			label: Some("Camera Uniform Buffer"),
@@ -177,3 +407,31 @@ impl RenderContext {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::RenderContext;
+
+	#[test]
+	fn readback_strips_padding_and_preserves_rgba() {
+		// 2×2 image, rows padded to 12 bytes (8 real + 4 padding).
+		let padded = 12;
+		let mapped = vec![
+			1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, // row 0 + padding
+			9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0, // row 1 + padding
+		];
+
+		let pixels = RenderContext::readback_to_rgba(&mapped, padded, 2, 2, false);
+		assert_eq!(pixels, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+	}
+
+	#[test]
+	fn readback_swaps_bgra_to_rgba() {
+		let padded = 8;
+		let mapped = vec![1, 2, 3, 4, 5, 6, 7, 8];
+
+		let pixels = RenderContext::readback_to_rgba(&mapped, padded, 2, 1, true);
+		// Blue/red channels swap, green and alpha stay put.
+		assert_eq!(pixels, vec![3, 2, 1, 4, 7, 6, 5, 8]);
+	}
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use wgpu::{Device, SurfaceConfiguration, TextureView};
+
+/// The WGSL shader used for the terminal fullscreen present pass.
+const PRESENT_SHADER: wgpu::ShaderModuleDescriptor = wgpu::include_wgsl!("./shaders/present.wgsl");
+
+/// The format intermediate graph slots are allocated with. Matching the HDR
+/// target lets offscreen passes keep values above `1.0` until a tonemap/present
+/// pass compresses them. The [`PRESENT_SLOT`] instead uses the surface format so
+/// the terminal blit matches the swapchain.
+const SLOT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// A named texture attachment threaded between passes. A pass lists the slots it
+/// samples as `inputs` and the slot it renders into as its `output`; the graph
+/// wires one pass's output into the next pass's input by name.
+pub type SlotId = &'static str;
+
+/// An intermediate render target owned by the graph, kept sized to the surface.
+struct SlotTexture {
+	view: TextureView,
+	/// Bind group exposing the slot as a sampled texture for the terminal
+	/// present pass.
+	sampled: wgpu::BindGroup,
+}
+
+/// A single node in the [`RenderGraph`]: the slots it reads, the slot it writes,
+/// and a closure that records its commands into the frame's encoder.
+///
+/// The closure receives the encoder, the view of its output slot, and the views
+/// of its declared input slots in order, so it begins its own render pass (with
+/// whatever depth/MSAA attachments it owns) and samples the previous pass's
+/// output. Camera and instance bind groups are captured by the closure, keeping
+/// them injectable per pass without the graph knowing their layouts.
+pub struct RenderGraphPass<'a> {
+	label: &'static str,
+	inputs: Vec<SlotId>,
+	output: SlotId,
+	record: Box<dyn Fn(&mut wgpu::CommandEncoder, &TextureView, &[&TextureView]) + 'a>,
+}
+
+impl<'a> RenderGraphPass<'a> {
+	pub fn new(
+		label: &'static str,
+		inputs: Vec<SlotId>,
+		output: SlotId,
+		record: impl Fn(&mut wgpu::CommandEncoder, &TextureView, &[&TextureView]) + 'a,
+	) -> Self {
+		Self { label, inputs, output, record: Box::new(record) }
+	}
+}
+
+/// A small multi-pass scheduler.
+///
+/// Passes are supplied per frame in any order and resolved into an execution
+/// order by slot dependency: a pass runs only once every slot it samples has
+/// been produced. Intermediate slot textures are owned by the graph, allocated
+/// on demand, sized to the surface, and reused across frames;
+/// [`RenderGraph::resize`] reallocates them. Every frame ends by blitting the
+/// [`PRESENT_SLOT`] onto the swapchain.
+pub struct RenderGraph {
+	slots: HashMap<SlotId, SlotTexture>,
+	sampled_layout: wgpu::BindGroupLayout,
+	sampler: wgpu::Sampler,
+	present_pipeline: wgpu::RenderPipeline,
+	surface_format: wgpu::TextureFormat,
+}
+
+/// The slot whose contents are blitted onto the swapchain by the present pass.
+pub const PRESENT_SLOT: SlotId = "present";
+
+impl RenderGraph {
+	pub fn new(device: &Device, surface_configuration: &SurfaceConfiguration) -> Self {
+		let sampled_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: Some("Graph Slot Bind Group Layout"),
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+			],
+		});
+
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			label: Some("Graph Slot Sampler"),
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			..Default::default()
+		});
+
+		let present_pipeline = Self::create_present_pipeline(device, &sampled_layout, surface_configuration);
+
+		Self {
+			slots: HashMap::new(),
+			sampled_layout,
+			sampler,
+			present_pipeline,
+			surface_format: surface_configuration.format,
+		}
+	}
+
+	/// Reallocate every slot texture to match the resized surface.
+	pub fn resize(&mut self, device: &Device, surface_configuration: &SurfaceConfiguration) {
+		self.surface_format = surface_configuration.format;
+		let slots: Vec<SlotId> = self.slots.keys().copied().collect();
+		self.slots.clear();
+		for slot in slots {
+			self.ensure_slot(device, surface_configuration, slot);
+		}
+	}
+
+	/// Resolve the pass order, record every pass into `encoder`, and finish by
+	/// blitting the [`PRESENT_SLOT`] onto `surface_view`. Slot textures for the
+	/// passes' inputs and outputs are allocated on first reference.
+	pub fn execute(
+		&mut self,
+		device: &Device,
+		surface_configuration: &SurfaceConfiguration,
+		encoder: &mut wgpu::CommandEncoder,
+		surface_view: &TextureView,
+		passes: &[RenderGraphPass],
+	) {
+		for pass in passes {
+			self.ensure_slot(device, surface_configuration, pass.output);
+			for input in &pass.inputs {
+				self.ensure_slot(device, surface_configuration, input);
+			}
+		}
+		self.ensure_slot(device, surface_configuration, PRESENT_SLOT);
+
+		for pass in Self::resolve_order(passes) {
+			let output = &self.slots[pass.output].view;
+			let inputs: Vec<&TextureView> = pass.inputs.iter().map(|slot| &self.slots[slot].view).collect();
+			encoder.push_debug_group(pass.label);
+			(pass.record)(encoder, output, &inputs);
+			encoder.pop_debug_group();
+		}
+
+		// Terminal present: blit the final slot onto the swapchain.
+		let mut present_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Graph Present Pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view: surface_view,
+				resolve_target: None,
+				ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+			})],
+			depth_stencil_attachment: None,
+			timestamp_writes: None,
+			occlusion_query_set: None,
+		});
+		present_pass.set_pipeline(&self.present_pipeline);
+		present_pass.set_bind_group(0, &self.slots[PRESENT_SLOT].sampled, &[]);
+		present_pass.draw(0..3, 0..1);
+	}
+
+	/// Order the passes so each runs only after every slot it samples has been
+	/// produced. A pass whose inputs are all external (unproduced) slots is ready
+	/// immediately; the rest schedule as their producers complete.
+	fn resolve_order<'b, 'a>(passes: &'b [RenderGraphPass<'a>]) -> Vec<&'b RenderGraphPass<'a>> {
+		let mut produced: Vec<SlotId> = Vec::new();
+		let mut ordered = Vec::with_capacity(passes.len());
+		let mut remaining: Vec<&RenderGraphPass<'a>> = passes.iter().collect();
+
+		while !remaining.is_empty() {
+			let ready = remaining.iter().position(|pass| {
+				pass.inputs
+					.iter()
+					.all(|input| produced.contains(input) || !passes.iter().any(|other| other.output == *input))
+			});
+
+			// A dependency cycle leaves nothing ready; fall back to declaration
+			// order for the remainder so the graph still makes progress.
+			let index = ready.unwrap_or(0);
+			let pass = remaining.remove(index);
+			produced.push(pass.output);
+			ordered.push(pass);
+		}
+
+		ordered
+	}
+
+	fn ensure_slot(&mut self, device: &Device, surface_configuration: &SurfaceConfiguration, slot: SlotId) {
+		if self.slots.contains_key(slot) {
+			return;
+		}
+
+		// The present slot is blitted straight onto the swapchain, so it matches
+		// the surface format; intermediate slots stay in the HDR slot format.
+		let format = if slot == PRESENT_SLOT { self.surface_format } else { SLOT_FORMAT };
+
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some(slot),
+			size: wgpu::Extent3d {
+				width: surface_configuration.width,
+				height: surface_configuration.height,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+			view_formats: &[],
+		});
+
+		let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+		let sampled = device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some(slot),
+			layout: &self.sampled_layout,
+			entries: &[
+				wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+				wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+			],
+		});
+
+		self.slots.insert(slot, SlotTexture { view, sampled });
+	}
+
+	fn create_present_pipeline(
+		device: &Device,
+		sampled_layout: &wgpu::BindGroupLayout,
+		surface_configuration: &SurfaceConfiguration,
+	) -> wgpu::RenderPipeline {
+		let shader = device.create_shader_module(PRESENT_SHADER);
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Graph Present Pipeline Layout"),
+			bind_group_layouts: &[sampled_layout],
+			push_constant_ranges: &[],
+		});
+
+		device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some("Graph Present Pipeline"),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				compilation_options: Default::default(),
+				entry_point: Some("vs_main"),
+				buffers: &[],
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				compilation_options: Default::default(),
+				entry_point: Some("fs_main"),
+				targets: &[Some(wgpu::ColorTargetState {
+					format: surface_configuration.format,
+					blend: Some(wgpu::BlendState::REPLACE),
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+			}),
+			primitive: wgpu::PrimitiveState {
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				..Default::default()
+			},
+			multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+			multiview: None,
+			depth_stencil: None,
+			cache: None,
+		})
+	}
+}
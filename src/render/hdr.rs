@@ -0,0 +1,185 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{Device, Queue, SurfaceConfiguration, TextureView};
+
+/// The WGSL shader used for the tonemapping present pass.
+const TONEMAP_SHADER: wgpu::ShaderModuleDescriptor = wgpu::include_wgsl!("./shaders/tonemap.wgsl");
+
+/// The floating-point format of the offscreen HDR color target. Values may
+/// exceed `1.0` so additive/glow sprites keep their energy until the tonemap
+/// pass compresses them.
+const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// The tonemapping operator applied when compressing HDR colors down to the
+/// low-dynamic-range surface.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TonemapOperator {
+	#[default]
+	Reinhard,
+	Aces,
+}
+
+/// Configuration passed to the tonemap shader each frame.
+#[repr(C)]
+#[derive(Pod, Zeroable, Copy, Clone, Debug)]
+struct TonemapUniform {
+	operator: u32,
+	exposure: f32,
+	_padding: [f32; 2],
+}
+
+/// The fullscreen pass that resolves an HDR source texture onto an LDR target
+/// with tonemapping. The HDR target itself is owned by the [render
+/// graph](crate::render::graph) as a slot; this pass samples whichever slot it
+/// is handed each frame.
+pub struct HdrPipeline {
+	pipeline: wgpu::RenderPipeline,
+	bind_group_layout: wgpu::BindGroupLayout,
+	sampler: wgpu::Sampler,
+	uniform_buffer: wgpu::Buffer,
+}
+
+impl HdrPipeline {
+	pub fn new(device: &Device, surface_configuration: &SurfaceConfiguration) -> Self {
+		let shader = device.create_shader_module(TONEMAP_SHADER);
+
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			label: Some("HDR Sampler"),
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			..Default::default()
+		});
+
+		let uniform_buffer = device.create_buffer_init(&BufferInitDescriptor {
+			label: Some("Tonemap Uniform Buffer"),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			contents: bytemuck::bytes_of(&TonemapUniform {
+				operator: TonemapOperator::default() as u32,
+				exposure: 1.0,
+				_padding: [0.0; 2],
+			}),
+		});
+
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: Some("Tonemap Bind Group Layout"),
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Buffer {
+						ty: wgpu::BufferBindingType::Uniform,
+						has_dynamic_offset: false,
+						min_binding_size: None,
+					},
+					count: None,
+				},
+			],
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Tonemap Pipeline Layout"),
+			bind_group_layouts: &[&bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some("Tonemap Pipeline"),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				compilation_options: Default::default(),
+				entry_point: Some("vs_main"),
+				buffers: &[],
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				compilation_options: Default::default(),
+				entry_point: Some("fs_main"),
+				targets: &[Some(wgpu::ColorTargetState {
+					format: surface_configuration.format,
+					blend: Some(wgpu::BlendState::REPLACE),
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+			}),
+			primitive: wgpu::PrimitiveState {
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				..Default::default()
+			},
+			multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+			multiview: None,
+			depth_stencil: None,
+			cache: None,
+		});
+
+		Self { pipeline, bind_group_layout, sampler, uniform_buffer }
+	}
+
+	/// The floating-point format of the HDR target, used when building pipelines
+	/// that render into it.
+	pub fn format() -> wgpu::TextureFormat {
+		HDR_FORMAT
+	}
+
+	/// Update the tonemap operator and exposure scalar used for the next frame.
+	pub fn set_tonemap(&self, queue: &Queue, operator: TonemapOperator, exposure: f32) {
+		let uniform = TonemapUniform { operator: operator as u32, exposure, _padding: [0.0; 2] };
+		queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+	}
+
+	/// Sample the HDR `source` and resolve it onto `output` with tonemapping. The
+	/// bind group is rebuilt each call so the pass can target whichever graph slot
+	/// currently holds the scene.
+	pub fn tonemap(
+		&self,
+		device: &Device,
+		encoder: &mut wgpu::CommandEncoder,
+		source: &TextureView,
+		output: &TextureView,
+	) {
+		let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some("Tonemap Bind Group"),
+			layout: &self.bind_group_layout,
+			entries: &[
+				wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source) },
+				wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+				wgpu::BindGroupEntry { binding: 2, resource: self.uniform_buffer.as_entire_binding() },
+			],
+		});
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Tonemap Pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view: output,
+				resolve_target: None,
+				ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+			})],
+			depth_stencil_attachment: None,
+			timestamp_writes: None,
+			occlusion_query_set: None,
+		});
+
+		render_pass.set_pipeline(&self.pipeline);
+		render_pass.set_bind_group(0, &bind_group, &[]);
+		render_pass.draw(0..3, 0..1);
+	}
+}
@@ -1,9 +1,17 @@
 pub(crate) mod camera;
+mod compute;
 mod context;
+mod graph;
+mod hdr;
+mod pool;
 mod sprite;
 mod texture;
 mod util;
 mod vertex;
+mod video;
 
 pub(crate) use self::context::RenderContext;
+pub(crate) use self::hdr::TonemapOperator;
+pub(crate) use self::pool::TextureHandle;
 pub(crate) use self::sprite::Sprite;
+pub(crate) use self::video::{VideoFormat, VideoTexture};
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use wgpu::{BindGroupLayout, Device, Queue};
+
+use crate::render::texture::{Texture, TextureOptions};
+use crate::render::util::TextureBinding;
+
+/// Every texture the pool loads from bytes requests a full mipmap chain so
+/// minified sprites do not shimmer.
+const LOADED_TEXTURE_OPTIONS: TextureOptions = TextureOptions { generate_mipmaps: true };
+
+/// The texture used when an asset cannot be resolved from disk. Embedding it
+/// guarantees the pool always has a valid binding to fall back on.
+const DEFAULT_TEXTURE: &[u8] = include_bytes!("../../assets/test_sprite.png");
+
+/// Lightweight, copyable handle identifying a texture owned by a
+/// [`TexturePool`]. Sprites store this instead of a path so the renderer can
+/// group draws by texture without hashing strings every frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextureHandle(usize);
+
+/// Owns every GPU texture in the scene and deduplicates loads by path.
+///
+/// The pool hands back a [`TextureHandle`] the first time a path is requested
+/// and returns the same handle on subsequent requests, so an image is only
+/// uploaded to the GPU once regardless of how many sprites reference it.
+pub struct TexturePool {
+	by_path: HashMap<String, TextureHandle>,
+	bindings: Vec<Arc<TextureBinding>>,
+	/// The single bind group layout every [`TextureBinding`] in the pool is
+	/// created against, so each one stays compatible with the sprite pipeline
+	/// layout built around it. See [`TextureBinding::create_layout`].
+	bind_group_layout: Arc<BindGroupLayout>,
+}
+
+impl TexturePool {
+	pub fn new(device: &Device, queue: &Queue) -> Self {
+		let bind_group_layout = Arc::new(TextureBinding::create_layout(device));
+
+		let texture = Texture::from_bytes_with_options(device, queue, DEFAULT_TEXTURE, LOADED_TEXTURE_OPTIONS)
+			.expect("Failed to decode default texture.");
+		let default = TextureBinding::create(device, Some("Default Texture".into()), &bind_group_layout, texture);
+
+		// A 1×1 white texture so flat-colored sprites reuse the sprite pipeline:
+		// the per-instance tint multiplies this white texel to produce the color.
+		let white = Texture::from_rgba(device, queue, 1, 1, &[255, 255, 255, 255]);
+		let white = TextureBinding::create(device, Some("White Texture".into()), &bind_group_layout, white);
+
+		Self { by_path: HashMap::new(), bindings: vec![Arc::new(default), Arc::new(white)], bind_group_layout }
+	}
+
+	/// Resolve `path` to a texture handle, uploading the image on first use.
+	///
+	/// A failed load is logged once and resolved to the default texture; the
+	/// mapping is cached either way so the disk is not touched again.
+	pub fn load(&mut self, device: &Device, queue: &Queue, path: &str) -> TextureHandle {
+		if let Some(handle) = self.by_path.get(path) {
+			return *handle;
+		}
+
+		let handle = match std::fs::read(path).map_err(|error| error.to_string()).and_then(|bytes| {
+			Texture::from_bytes_with_options(device, queue, &bytes, LOADED_TEXTURE_OPTIONS).map_err(|error| error.to_string())
+		}) {
+			Ok(texture) => {
+				let binding =
+					TextureBinding::create(device, Some(format!("{path} Texture")), &self.bind_group_layout, texture);
+				self.bindings.push(Arc::new(binding));
+				TextureHandle(self.bindings.len() - 1)
+			}
+			Err(error) => {
+				tracing::warn!("Failed to load texture '{path}', using default: {error}");
+				self.default_handle()
+			}
+		};
+
+		self.by_path.insert(path.to_owned(), handle);
+		handle
+	}
+
+	/// Register an already-decoded texture and return a stable handle for it.
+	///
+	/// Unlike [`TexturePool::load`] this does not touch the disk or deduplicate,
+	/// so callers assembling an atlas/registry up front can upload generated or
+	/// streamed textures and keep the returned handle on their sprites.
+	pub fn register(&mut self, device: &Device, label: Option<String>, texture: Texture) -> TextureHandle {
+		let binding = TextureBinding::create(device, label, &self.bind_group_layout, texture);
+		self.bindings.push(Arc::new(binding));
+		TextureHandle(self.bindings.len() - 1)
+	}
+
+	/// Register an already-built, shared [`TextureBinding`] and return a stable
+	/// handle for it.
+	///
+	/// This lets a source that owns its own binding — such as a
+	/// [`VideoTexture`](crate::render::video::VideoTexture) streaming frames into
+	/// a texture it reallocates on resize — share that binding with the pool. The
+	/// pool holds a clone of the `Arc`, so frames written in place are visible to
+	/// any sprite holding the returned handle. `binding` must have been created
+	/// against this pool's [`TexturePool::bind_group_layout`] (e.g. by passing it
+	/// into [`VideoTexture::new`](crate::render::video::VideoTexture::new)), or
+	/// the sprite pipeline will reject the bind group at draw time.
+	pub fn register_binding(&mut self, binding: Arc<TextureBinding>) -> TextureHandle {
+		self.bindings.push(binding);
+		TextureHandle(self.bindings.len() - 1)
+	}
+
+	/// The handle of the fallback texture, always present in the pool.
+	pub fn default_handle(&self) -> TextureHandle {
+		TextureHandle(0)
+	}
+
+	/// The handle of the 1×1 white texture used to render flat-colored sprites.
+	pub fn white_handle(&self) -> TextureHandle {
+		TextureHandle(1)
+	}
+
+	pub fn binding(&self, handle: TextureHandle) -> &Arc<TextureBinding> {
+		&self.bindings[handle.0]
+	}
+
+	/// The texture bind group layout shared by every binding in the pool, used to
+	/// build the sprite pipeline layout.
+	pub fn bind_group_layout(&self) -> &BindGroupLayout {
+		&self.bind_group_layout
+	}
+}
@@ -1,14 +1,59 @@
 use bytemuck::{Pod, Zeroable};
 use glam::Mat4;
 
+/// Sample the instance's texture as ordinary RGBA.
+pub const SAMPLE_MODE_RGBA: u32 = 0;
+/// Sample the instance's texture as NV12: luma from the first plane and chroma
+/// from the second, converted to RGB in the shader.
+pub const SAMPLE_MODE_NV12: u32 = 1;
+
+/// Per-instance data uploaded to the GPU for a single sprite.
+///
+/// The data is streamed through a [`wgpu::VertexStepMode::Instance`] vertex
+/// buffer so the whole batch is issued with one instanced draw call. A
+/// [`Mat4`] cannot be a single vertex attribute, so it occupies four
+/// consecutive `Float32x4` locations that the vertex shader reassembles into a
+/// model matrix.
 #[repr(C)]
 #[derive(Pod, Zeroable, Clone, Copy, Debug)]
 pub struct SpriteInstanceData {
 	transform: Mat4,
+	tint: [f32; 4],
+	/// Sub-rectangle of the texture sampled by this instance, stored as
+	/// `[origin_x, origin_y, width, height]` in normalized coordinates. The full
+	/// texture is `[0.0, 0.0, 1.0, 1.0]`; a smaller rect selects one cell of a
+	/// sprite sheet.
+	uv_rect: [f32; 4],
+	/// How the fragment shader interprets the bound texture: `SAMPLE_MODE_RGBA`
+	/// samples it directly, `SAMPLE_MODE_NV12` converts YUV planes to RGB.
+	sample_mode: u32,
+	/// Pads the struct to a 16-byte multiple so it satisfies `bytemuck::Pod`.
+	_padding: [u32; 3],
 }
 
 impl SpriteInstanceData {
-	pub fn new(transform: Mat4) -> Self {
-		Self { transform }
+	pub fn new(transform: Mat4, tint: [f32; 4], uv_rect: [f32; 4], sample_mode: u32) -> Self {
+		Self { transform, tint, uv_rect, sample_mode, _padding: [0; 3] }
+	}
+
+	/// Describes the instance step-mode vertex buffer layout for the sprite
+	/// pipeline. The four `Float32x4` slots for the model matrix start at
+	/// location 5 (a `Mat4` cannot be a single attribute), followed by the RGBA
+	/// tint at location 9, the UV sub-rect at location 10, and the sample-mode
+	/// flag at location 11.
+	pub fn vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+		wgpu::VertexBufferLayout {
+			array_stride: size_of::<Self>() as u64,
+			step_mode: wgpu::VertexStepMode::Instance,
+			attributes: &wgpu::vertex_attr_array![
+				5 => Float32x4,
+				6 => Float32x4,
+				7 => Float32x4,
+				8 => Float32x4,
+				9 => Float32x4,
+				10 => Float32x4,
+				11 => Uint32,
+			],
+		}
 	}
 }
@@ -1,20 +1,72 @@
 mod instance;
 mod pipeline;
 
-use self::instance::SpriteInstanceData;
+pub(crate) use self::instance::SpriteInstanceData;
+pub(crate) use self::instance::{SAMPLE_MODE_NV12, SAMPLE_MODE_RGBA};
 pub(super) use self::pipeline::SpritePipeline;
+use crate::render::pool::TextureHandle;
 
 /// Component describing a sprite rendered by the sprite pipeline.
-pub struct Sprite {}
+pub struct Sprite {
+	/// A texture the caller pre-registered with the pool. When set this takes
+	/// precedence over [`Sprite::image`], letting sprites share a registry handle
+	/// without re-resolving a path every frame.
+	pub(crate) texture: Option<TextureHandle>,
+	/// Path of the image backing this sprite, resolved to a
+	/// [`TextureHandle`](crate::render::pool::TextureHandle) by the renderer's
+	/// texture pool on first use. `None` renders flat-colored against the 1×1
+	/// white texture.
+	pub(crate) image: Option<String>,
+	/// RGBA multiply factor applied to the sampled texel, letting sprites be
+	/// tinted or faded. White (`[1.0; 4]`) leaves the texture unchanged.
+	pub(crate) tint: [f32; 4],
+	/// Sub-rectangle of the backing texture this sprite samples, stored as
+	/// `[origin_x, origin_y, width, height]` in normalized coordinates. The
+	/// default `[0.0, 0.0, 1.0, 1.0]` samples the whole texture; a smaller rect
+	/// selects a single cell of a sprite sheet.
+	pub(crate) uv_rect: [f32; 4],
+	/// How the fragment shader interprets the bound texture, one of the
+	/// `SAMPLE_MODE_*` constants. Defaults to RGBA; a sprite backed by an NV12
+	/// [`VideoTexture`](crate::render::video::VideoTexture) selects the YUV path.
+	pub(crate) sample_mode: u32,
+}
+
+/// The UV sub-rect covering the entire texture.
+const FULL_UV_RECT: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
 
 impl Sprite {
-	// TODO: The path will automatically reference an asset from the asset loader.
 	pub fn from_image(path: &str) -> Self {
-		tracing::info!("Created sprite {path}");
-		Self {}
+		Self { texture: None, image: Some(path.to_owned()), tint: [1.0; 4], uv_rect: FULL_UV_RECT, sample_mode: SAMPLE_MODE_RGBA }
+	}
+
+	/// Build a sprite from a texture already registered with the renderer's
+	/// pool, identified by its stable [`TextureHandle`].
+	pub fn from_texture(handle: TextureHandle) -> Self {
+		Self { texture: Some(handle), image: None, tint: [1.0; 4], uv_rect: FULL_UV_RECT, sample_mode: SAMPLE_MODE_RGBA }
 	}
 
 	pub fn from_color(color: wgpu::Color) -> Self {
-		Self {}
+		Self {
+			texture: None,
+			image: None,
+			tint: [color.r as f32, color.g as f32, color.b as f32, color.a as f32],
+			uv_rect: FULL_UV_RECT,
+			sample_mode: SAMPLE_MODE_RGBA,
+		}
+	}
+
+	/// Restrict this sprite to a rectangular region of its texture, given as an
+	/// origin and size in normalized coordinates. Use this to point an entity at
+	/// a single cell of a sprite sheet.
+	pub fn with_region(mut self, origin: [f32; 2], size: [f32; 2]) -> Self {
+		self.uv_rect = [origin[0], origin[1], size[0], size[1]];
+		self
+	}
+
+	/// Set how the bound texture is sampled, one of the `SAMPLE_MODE_*`
+	/// constants. Use this to render an NV12 [`VideoTexture`] sprite.
+	pub fn with_sample_mode(mut self, sample_mode: u32) -> Self {
+		self.sample_mode = sample_mode;
+		self
 	}
 }
@@ -1,11 +1,11 @@
-use glam::{Mat4, Vec2};
+use glam::Vec2;
 use hecs::World;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPipeline};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
 
+use crate::render::pool::{TextureHandle, TexturePool};
 use crate::render::sprite::{Sprite, SpriteInstanceData};
-use crate::render::texture::Texture;
-use crate::render::util::{StorageBinding, TextureBinding, UniformBinding};
+use crate::render::util::StorageBinding;
 use crate::render::vertex::Vertex;
 use crate::transform::Transform;
 
@@ -23,21 +23,54 @@ const SPRITE_VERTICES: &[Vertex] = &[
 /// Index array to define a quad from the [`SPRITE_VERTICES`] vertex array.
 const SPRITE_INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
 
-/// The maximum number of instances that will be rendered per frame.
-const MAX_INSTANCES: u64 = 10_000;
+/// Initial instance-buffer capacity. The growable [`StorageBinding`] grows to
+/// the smallest power of two that fits whenever a frame exceeds it, so the worst
+/// case is never preallocated.
+const INITIAL_INSTANCE_CAPACITY: u64 = 256;
+
+/// Depth format backing the sprite pipeline's layering buffer.
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The color the sprite pass clears its target to at the start of the frame.
+const CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.01, g: 0.01, b: 0.01, a: 1.0 };
+
+/// A contiguous range of instances in the instance buffer that all share a
+/// single texture and can therefore be issued with one instanced draw call.
+struct InstanceGroup {
+	handle: TextureHandle,
+	range: std::ops::Range<u32>,
+}
 
 /// This pipeline is used for rendering sprites in the scene.
 pub struct SpritePipeline {
-	pipeline: RenderPipeline,
+	/// Draws opaque sprites (`tint.a == 1.0`) and writes depth so translucent
+	/// sprites behind them are occluded.
+	opaque_pipeline: RenderPipeline,
+	/// Draws alpha-blended sprites. Reads the depth buffer the opaque pass
+	/// wrote but does not write to it, so two overlapping translucent sprites
+	/// blend instead of the farther one being depth-culled by the nearer one.
+	translucent_pipeline: RenderPipeline,
 	vertex_buffer: Buffer,
 	index_buffer: Buffer,
-	instance_binding: StorageBinding<SpriteInstanceData>,
-	instance_count: u32,
-	texture_binding: TextureBinding,
+	instances: StorageBinding<SpriteInstanceData>,
+	opaque_groups: Vec<InstanceGroup>,
+	translucent_groups: Vec<InstanceGroup>,
+	depth_view: TextureView,
+	/// Multisampled intermediate color target the pass renders into and resolves
+	/// from. `None` when `sample_count` is 1 and the pass draws straight into its
+	/// output view.
+	msaa_view: Option<TextureView>,
+	sample_count: u32,
 }
 
 impl SpritePipeline {
-	pub fn new(device: &Device, queue: &Queue, camera_bind_layout: &BindGroupLayout) -> Self {
+	pub fn new(
+		device: &Device,
+		camera_bind_layout: &BindGroupLayout,
+		texture_bind_layout: &BindGroupLayout,
+		surface_configuration: &SurfaceConfiguration,
+		sample_count: u32,
+	) -> Self {
 		let shader = device.create_shader_module(SPRITE_SHADER);
 
 		let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
@@ -52,41 +85,75 @@ impl SpritePipeline {
 			contents: bytemuck::cast_slice(SPRITE_INDICES),
 		});
 
-		let instance_binding = StorageBinding::create(device, Some("Sprite Instance Buffer".into()), MAX_INSTANCES);
-
-		let texture = Texture::from_bytes(device, queue, include_bytes!("../../../assets/test_sprite.png")).unwrap();
-		let texture_binding = TextureBinding::create(device, Some("Test Texture".into()), texture);
+		let instances =
+			StorageBinding::create_growable(device, Some("Sprite Instance Buffer".into()), INITIAL_INSTANCE_CAPACITY);
 
 		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
 			label: Some("Sprite Pipeline Layout"),
-			bind_group_layouts: &[
-				camera_bind_layout,
-				instance_binding.bind_group_layout(),
-				texture_binding.bind_group_layout(),
-			],
+			bind_group_layouts: &[camera_bind_layout, texture_bind_layout],
 			push_constant_ranges: &[],
 		});
 
-		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-			label: Some("Sprite Pipeline"),
-			layout: Some(&pipeline_layout),
+		let opaque_pipeline =
+			Self::create_pipeline(device, &pipeline_layout, &shader, sample_count, "Sprite Opaque Pipeline", true);
+		let translucent_pipeline =
+			Self::create_pipeline(device, &pipeline_layout, &shader, sample_count, "Sprite Translucent Pipeline", false);
+
+		let depth_view = Self::create_depth_view(device, surface_configuration, sample_count);
+		let msaa_view = Self::create_msaa_view(device, surface_configuration, sample_count);
+
+		Self {
+			opaque_pipeline,
+			translucent_pipeline,
+			vertex_buffer,
+			index_buffer,
+			instances,
+			opaque_groups: Vec::new(),
+			translucent_groups: Vec::new(),
+			depth_view,
+			msaa_view,
+			sample_count,
+		}
+	}
+
+	/// Build one of the opaque/translucent render pipeline variants. Both share
+	/// every piece of state except `depth_write_enabled`, so this factors out
+	/// the descriptor the two calls in [`SpritePipeline::new`] would otherwise
+	/// duplicate.
+	fn create_pipeline(
+		device: &Device,
+		pipeline_layout: &wgpu::PipelineLayout,
+		shader: &wgpu::ShaderModule,
+		sample_count: u32,
+		label: &str,
+		depth_write: bool,
+	) -> RenderPipeline {
+		device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some(label),
+			layout: Some(pipeline_layout),
 			vertex: wgpu::VertexState {
-				module: &shader,
+				module: shader,
 				compilation_options: Default::default(),
 				entry_point: Some("vs_main"),
-				buffers: &[wgpu::VertexBufferLayout {
-					array_stride: size_of::<Vertex>() as u64,
-					step_mode: wgpu::VertexStepMode::Vertex,
-					attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
-				}],
+				buffers: &[
+					wgpu::VertexBufferLayout {
+						array_stride: size_of::<Vertex>() as u64,
+						step_mode: wgpu::VertexStepMode::Vertex,
+						attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+					},
+					SpriteInstanceData::vertex_buffer_layout(),
+				],
 			},
 			fragment: Some(wgpu::FragmentState {
-				module: &shader,
+				module: shader,
 				compilation_options: Default::default(),
 				entry_point: Some("fs_main"),
 				targets: &[Some(wgpu::ColorTargetState {
-					format: wgpu::TextureFormat::Bgra8UnormSrgb,
-					blend: Some(wgpu::BlendState::REPLACE),
+					format: crate::render::hdr::HdrPipeline::format(),
+					// Alpha-composite so the per-instance tint's alpha, `from_color`
+					// with `a < 1.0`, and transparent texel regions actually blend
+					// over what is already in the target instead of overwriting it.
+					blend: Some(wgpu::BlendState::ALPHA_BLENDING),
 					write_mask: wgpu::ColorWrites::ALL,
 				})],
 			}),
@@ -99,34 +166,200 @@ impl SpritePipeline {
 				unclipped_depth: false,
 				conservative: false,
 			},
-			multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+			multisample: wgpu::MultisampleState { count: sample_count, mask: !0, alpha_to_coverage_enabled: false },
 			multiview: None,
-			depth_stencil: None,
+			// Opaque sprites write depth; the alpha-blended pass reads it without
+			// writing so translucent sprites do not occlude one another.
+			depth_stencil: Some(wgpu::DepthStencilState {
+				format: DEPTH_FORMAT,
+				depth_write_enabled: depth_write,
+				depth_compare: wgpu::CompareFunction::LessEqual,
+				stencil: wgpu::StencilState::default(),
+				bias: wgpu::DepthBiasState::default(),
+			}),
 			cache: None,
+		})
+	}
+
+	/// Recreate the depth and multisampled color buffers to match the resized
+	/// surface.
+	pub fn resize(&mut self, device: &Device, surface_configuration: &SurfaceConfiguration) {
+		self.depth_view = Self::create_depth_view(device, surface_configuration, self.sample_count);
+		self.msaa_view = Self::create_msaa_view(device, surface_configuration, self.sample_count);
+	}
+
+	/// Allocate the multisampled color target, or `None` when multisampling is
+	/// disabled. Its format matches the HDR color target the pass resolves into.
+	fn create_msaa_view(
+		device: &Device,
+		surface_configuration: &SurfaceConfiguration,
+		sample_count: u32,
+	) -> Option<TextureView> {
+		if sample_count <= 1 {
+			return None;
+		}
+
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Sprite MSAA Color Target"),
+			size: wgpu::Extent3d {
+				width: surface_configuration.width,
+				height: surface_configuration.height,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count,
+			dimension: wgpu::TextureDimension::D2,
+			format: crate::render::hdr::HdrPipeline::format(),
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+			view_formats: &[],
 		});
 
-		Self { pipeline, vertex_buffer, index_buffer, instance_binding, instance_count: 0, texture_binding }
+		Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
 	}
 
-	pub fn prepare(&mut self, queue: &Queue, world: &mut World) {
-		let mut instances = Vec::new();
+	fn create_depth_view(
+		device: &Device,
+		surface_configuration: &SurfaceConfiguration,
+		sample_count: u32,
+	) -> TextureView {
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Sprite Depth Texture"),
+			size: wgpu::Extent3d {
+				width: surface_configuration.width,
+				height: surface_configuration.height,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count,
+			dimension: wgpu::TextureDimension::D2,
+			format: DEPTH_FORMAT,
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+			view_formats: &[],
+		});
+
+		texture.create_view(&wgpu::TextureViewDescriptor::default())
+	}
+
+	pub fn prepare(&mut self, device: &Device, queue: &Queue, pool: &mut TexturePool, world: &mut World) {
+		// Resolve each sprite to its texture handle, then sort by handle so
+		// sprites sharing a texture form one contiguous instance range that can
+		// be issued with a single instanced draw call.
+		let mut resolved = Vec::new();
 		let mut query = world.query::<(&Transform, &Sprite)>();
 		for (_, (transform, sprite)) in query.iter() {
-			let instance = SpriteInstanceData::new(transform.matrix());
+			let handle = match (&sprite.texture, &sprite.image) {
+				(Some(handle), _) => *handle,
+				(None, Some(path)) => pool.load(device, queue, path),
+				(None, None) => pool.white_handle(),
+			};
+			// A sprite with `tint.a < 1.0` (plain alpha fade or `from_color` with
+			// a translucent color) goes through the depth-read-only pass so it
+			// blends instead of being depth-culled by whatever else is drawn.
+			let translucent = sprite.tint[3] < 1.0;
+			resolved.push((
+				handle,
+				translucent,
+				SpriteInstanceData::new(transform.matrix(), sprite.tint, sprite.uv_rect, sprite.sample_mode),
+			));
+		}
+		// Partition opaque before translucent, then sort each partition by
+		// handle so it still batches into contiguous instanced draw ranges.
+		resolved.sort_by_key(|(handle, translucent, _)| (*translucent, *handle));
+
+		let mut instances = Vec::with_capacity(resolved.len());
+		self.opaque_groups.clear();
+		self.translucent_groups.clear();
+		for (handle, translucent, instance) in resolved {
+			let groups = if translucent { &mut self.translucent_groups } else { &mut self.opaque_groups };
+			match groups.last_mut() {
+				Some(group) if group.handle == handle => group.range.end += 1,
+				_ => {
+					let start = instances.len() as u32;
+					groups.push(InstanceGroup { handle, range: start..start + 1 });
+				}
+			}
 			instances.push(instance);
 		}
 
-		queue.write_buffer(self.instance_binding.buffer(), 0, bytemuck::cast_slice(&instances[..]));
-		self.instance_count = instances.len() as u32;
+		// The growable binding reallocates its buffer to the smallest power of
+		// two that fits if this frame holds more sprites than the current
+		// capacity, reusing it otherwise.
+		self.instances.write_slice(device, queue, &instances);
 	}
 
-	pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &BindGroup) {
-		render_pass.set_pipeline(&self.pipeline);
+	/// Record the sprite pass into `encoder`, rendering into `target`.
+	///
+	/// When multisampling is enabled the draws target the owned multisampled
+	/// color texture and resolve into `target`; otherwise they render into
+	/// `target` directly. The owned depth buffer is attached in both cases.
+	///
+	/// `particles`, when present, is a GPU-resident instance buffer produced by
+	/// the [particle compute pass](crate::render::compute) together with its
+	/// instance count; it is drawn against the 1×1 white texture in the same pass
+	/// so particles composite with the sprites without a CPU round-trip.
+	///
+	/// Opaque sprites draw first and write depth; translucent sprites draw
+	/// after against the same depth buffer without writing to it, so a
+	/// translucent sprite farther from the camera blends under a nearer one
+	/// instead of being fully depth-culled by it.
+	pub fn draw(
+		&self,
+		encoder: &mut wgpu::CommandEncoder,
+		target: &TextureView,
+		camera_bind_group: &BindGroup,
+		pool: &TexturePool,
+		particles: Option<(&Buffer, u32)>,
+	) {
+		let (view, resolve_target) = match &self.msaa_view {
+			Some(msaa_view) => (msaa_view, Some(target)),
+			None => (target, None),
+		};
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Sprite Pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view,
+				resolve_target,
+				ops: wgpu::Operations { load: wgpu::LoadOp::Clear(CLEAR_COLOR), store: wgpu::StoreOp::Store },
+			})],
+			depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+				view: &self.depth_view,
+				depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }),
+				stencil_ops: None,
+			}),
+			timestamp_writes: None,
+			occlusion_query_set: None,
+		});
+
 		render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
 		render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 		render_pass.set_bind_group(0, camera_bind_group, &[]);
-		render_pass.set_bind_group(1, self.instance_binding.bind_group(), &[]);
-		render_pass.set_bind_group(2, self.texture_binding.bind_group(), &[]);
-		render_pass.draw_indexed(0..6, 0, 0..self.instance_count);
+
+		render_pass.set_pipeline(&self.opaque_pipeline);
+		render_pass.set_vertex_buffer(1, self.instances.buffer().slice(..));
+		for group in &self.opaque_groups {
+			render_pass.set_bind_group(1, pool.binding(group.handle).bind_group(), &[]);
+			render_pass.draw_indexed(0..6, 0, group.range.clone());
+		}
+
+		// Draw the compute-simulated particles from their own instance buffer,
+		// tinting the white texture by each particle's color. Particles never
+		// fade their alpha below 1.0, so they draw opaque alongside the sprites.
+		if let Some((buffer, count)) = particles {
+			if count > 0 {
+				render_pass.set_vertex_buffer(1, buffer.slice(..));
+				render_pass.set_bind_group(1, pool.binding(pool.white_handle()).bind_group(), &[]);
+				render_pass.draw_indexed(0..6, 0, 0..count);
+			}
+		}
+
+		if !self.translucent_groups.is_empty() {
+			render_pass.set_pipeline(&self.translucent_pipeline);
+			render_pass.set_vertex_buffer(1, self.instances.buffer().slice(..));
+			for group in &self.translucent_groups {
+				render_pass.set_bind_group(1, pool.binding(group.handle).bind_group(), &[]);
+				render_pass.draw_indexed(0..6, 0, group.range.clone());
+			}
+		}
 	}
 }
@@ -1,6 +1,17 @@
 use image::{GenericImageView, ImageError};
 use wgpu::{Device, Queue, Sampler, Texture as WGPUTexture, TextureView};
 
+/// The WGSL shader used to downsample one mip level into the next.
+const BLIT_SHADER: wgpu::ShaderModuleDescriptor = wgpu::include_wgsl!("./shaders/blit.wgsl");
+
+/// Upload options for [`Texture::from_bytes_with_options`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TextureOptions {
+	/// Generate a full mipmap chain on upload so minified sprites do not
+	/// shimmer. When enabled the sampler also switches to trilinear filtering.
+	pub generate_mipmaps: bool,
+}
+
 pub struct Texture {
 	raw_texture: WGPUTexture,
 	pub view: TextureView,
@@ -9,6 +20,15 @@ pub struct Texture {
 
 impl Texture {
 	pub fn from_bytes(device: &Device, queue: &Queue, bytes: &[u8]) -> Result<Self, ImageError> {
+		Self::from_bytes_with_options(device, queue, bytes, TextureOptions::default())
+	}
+
+	pub fn from_bytes_with_options(
+		device: &Device,
+		queue: &Queue,
+		bytes: &[u8],
+		options: TextureOptions,
+	) -> Result<Self, ImageError> {
 		let texture_image = image::load_from_memory(bytes)?;
 		let texture_image_rgba = texture_image.to_rgba8();
 		let texture_image_dimensions = texture_image.dimensions();
@@ -19,14 +39,29 @@ impl Texture {
 			depth_or_array_layers: 1,
 		};
 
+		// A full chain has `1 + floor(log2(max(width, height)))` levels.
+		let mip_level_count = if options.generate_mipmaps {
+			1 + texture_image_dimensions.0.max(texture_image_dimensions.1).ilog2()
+		} else {
+			1
+		};
+
+		// Generating mips renders into each level, so the texture additionally
+		// needs to be usable as a render attachment.
+		let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+		if options.generate_mipmaps {
+			usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+		}
+
+		let format = wgpu::TextureFormat::Rgba8UnormSrgb;
 		let raw_texture = device.create_texture(&wgpu::TextureDescriptor {
 			label: Some("Diffuse Texture"),
 			size: texture_size,
-			mip_level_count: 1,
+			mip_level_count,
 			sample_count: 1,
 			dimension: wgpu::TextureDimension::D2,
-			format: wgpu::TextureFormat::Rgba8UnormSrgb,
-			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			format,
+			usage,
 			view_formats: &[],
 		});
 
@@ -46,6 +81,58 @@ impl Texture {
 			texture_size,
 		);
 
+		if mip_level_count > 1 {
+			Self::generate_mipmaps(device, queue, &raw_texture, format, mip_level_count);
+		}
+
+		let view = raw_texture.create_view(&wgpu::TextureViewDescriptor::default());
+		let (min_filter, mipmap_filter) = if options.generate_mipmaps {
+			(wgpu::FilterMode::Linear, wgpu::FilterMode::Linear)
+		} else {
+			(wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest)
+		};
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter,
+			mipmap_filter,
+			..Default::default()
+		});
+
+		Ok(Self { raw_texture, view, sampler })
+	}
+
+	/// Build a texture directly from raw, tightly-packed `Rgba8UnormSrgb`
+	/// pixels. Used for small generated textures such as the 1×1 white texture
+	/// that backs flat-colored sprites.
+	pub fn from_rgba(device: &Device, queue: &Queue, width: u32, height: u32, rgba: &[u8]) -> Self {
+		let texture_size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+
+		let raw_texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Raw Texture"),
+			size: texture_size,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: wgpu::TextureFormat::Rgba8UnormSrgb,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			view_formats: &[],
+		});
+
+		queue.write_texture(
+			wgpu::TexelCopyTextureInfo {
+				texture: &raw_texture,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+				mip_level: 0,
+			},
+			rgba,
+			wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(4 * width), rows_per_image: Some(height) },
+			texture_size,
+		);
+
 		let view = raw_texture.create_view(&wgpu::TextureViewDescriptor::default());
 		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
 			address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -57,6 +144,172 @@ impl Texture {
 			..Default::default()
 		});
 
-		Ok(Self { raw_texture, view, sampler })
+		Self { raw_texture, view, sampler }
+	}
+
+	/// Create an empty, re-uploadable texture of `format` sized `width`×`height`.
+	///
+	/// The contents are written later (and repeatedly) via [`Queue::write_texture`]
+	/// into [`Texture::raw`], so the texture carries `COPY_DST`. Used as the
+	/// per-frame destination for streaming sources such as decoded video planes.
+	pub fn empty(device: &Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+		let texture_size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+
+		let raw_texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Streaming Texture"),
+			size: texture_size,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			view_formats: &[],
+		});
+
+		let view = raw_texture.create_view(&wgpu::TextureViewDescriptor::default());
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			..Default::default()
+		});
+
+		Self { raw_texture, view, sampler }
+	}
+
+	/// The underlying WGPU texture, used to stream fresh pixels into an existing
+	/// allocation with [`Queue::write_texture`].
+	pub fn raw(&self) -> &WGPUTexture {
+		&self.raw_texture
+	}
+
+	/// Fills mip levels `1..mip_level_count` by repeatedly blitting level `i`
+	/// into level `i + 1` with a linear sampler.
+	fn generate_mipmaps(
+		device: &Device,
+		queue: &Queue,
+		texture: &WGPUTexture,
+		format: wgpu::TextureFormat,
+		mip_level_count: u32,
+	) {
+		let shader = device.create_shader_module(BLIT_SHADER);
+
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: Some("Mipmap Blit Bind Group Layout"),
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+			],
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Mipmap Blit Pipeline Layout"),
+			bind_group_layouts: &[&bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some("Mipmap Blit Pipeline"),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				compilation_options: Default::default(),
+				entry_point: Some("vs_main"),
+				buffers: &[],
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				compilation_options: Default::default(),
+				entry_point: Some("fs_main"),
+				targets: &[Some(wgpu::ColorTargetState {
+					format,
+					blend: Some(wgpu::BlendState::REPLACE),
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+			}),
+			primitive: wgpu::PrimitiveState {
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				..Default::default()
+			},
+			multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+			multiview: None,
+			depth_stencil: None,
+			cache: None,
+		});
+
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			..Default::default()
+		});
+
+		// One view per mip level so each level can be bound as a source and
+		// targeted as an attachment independently.
+		let views: Vec<TextureView> = (0..mip_level_count)
+			.map(|mip| {
+				texture.create_view(&wgpu::TextureViewDescriptor {
+					label: Some("Mipmap Level View"),
+					base_mip_level: mip,
+					mip_level_count: Some(1),
+					..Default::default()
+				})
+			})
+			.collect();
+
+		let mut encoder =
+			device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Mipmap Blit Encoder") });
+
+		for target_mip in 1..mip_level_count as usize {
+			let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+				label: Some("Mipmap Blit Bind Group"),
+				layout: &bind_group_layout,
+				entries: &[
+					wgpu::BindGroupEntry {
+						binding: 0,
+						resource: wgpu::BindingResource::TextureView(&views[target_mip - 1]),
+					},
+					wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
+				],
+			});
+
+			let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+				label: Some("Mipmap Blit Pass"),
+				color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+					view: &views[target_mip],
+					resolve_target: None,
+					ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+				})],
+				depth_stencil_attachment: None,
+				timestamp_writes: None,
+				occlusion_query_set: None,
+			});
+
+			render_pass.set_pipeline(&pipeline);
+			render_pass.set_bind_group(0, &bind_group, &[]);
+			render_pass.draw(0..3, 0..1);
+		}
+
+		queue.submit([encoder.finish()]);
 	}
 }
@@ -1,9 +1,30 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
-use wgpu::{BindGroup, BindGroupLayout, Buffer, Device};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue};
 
 use crate::render::texture::Texture;
 
+/// Buffer-usage, shader-visibility, and access options for a [`StorageBinding`].
+///
+/// The default matches a vertex-stage read-only storage buffer; compute
+/// bindings override these via [`StorageBinding::create_compute`].
+struct StorageBindingOptions {
+	usage: wgpu::BufferUsages,
+	visibility: wgpu::ShaderStages,
+	read_only: bool,
+}
+
+impl Default for StorageBindingOptions {
+	fn default() -> Self {
+		Self {
+			usage: wgpu::BufferUsages::STORAGE.union(wgpu::BufferUsages::COPY_DST),
+			visibility: wgpu::ShaderStages::VERTEX,
+			read_only: true,
+		}
+	}
+}
+
 /// Wrapper around a WGPU storage buffer.
 ///
 /// This wrapper is responsible for storing the [`wgpu::Buffer`] that contains
@@ -13,15 +34,65 @@ pub struct StorageBinding<T: Sized> {
 	buffer: Buffer,
 	bind_group_layout: BindGroupLayout,
 	bind_group: BindGroup,
+	label: Option<String>,
+	capacity: u64,
+	usage: wgpu::BufferUsages,
 	_marker: PhantomData<T>,
 }
 
 impl<T> StorageBinding<T> {
 	pub fn create(device: &Device, buffer_label: Option<String>, max_elements: u64) -> Self {
+		Self::create_with_capacity(device, buffer_label, max_elements, StorageBindingOptions::default())
+	}
+
+	/// Create a storage binding sized to `initial_elements` that grows on demand
+	/// via [`StorageBinding::write_slice`] instead of preallocating a worst-case
+	/// buffer up front.
+	///
+	/// The buffer additionally carries [`wgpu::BufferUsages::VERTEX`] so the same
+	/// growable allocation can be bound as an instance vertex buffer — the sprite
+	/// pipeline streams its per-instance data through one of these.
+	pub fn create_growable(device: &Device, buffer_label: Option<String>, initial_elements: u64) -> Self {
+		Self::create_with_capacity(
+			device,
+			buffer_label,
+			initial_elements.max(1),
+			StorageBindingOptions {
+				usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+				..StorageBindingOptions::default()
+			},
+		)
+	}
+
+	/// Create a read-write storage binding visible to the compute stage.
+	///
+	/// The buffer additionally carries [`wgpu::BufferUsages::VERTEX`] so the same
+	/// storage can be bound as an instance vertex buffer by a later render pass
+	/// without a CPU round-trip — a compute shader mutates the data in place and
+	/// the vertex stage reads it directly.
+	pub fn create_compute(device: &Device, buffer_label: Option<String>, max_elements: u64) -> Self {
+		Self::create_with_capacity(
+			device,
+			buffer_label,
+			max_elements,
+			StorageBindingOptions {
+				usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+				visibility: wgpu::ShaderStages::COMPUTE,
+				read_only: false,
+			},
+		)
+	}
+
+	fn create_with_capacity(
+		device: &Device,
+		buffer_label: Option<String>,
+		capacity: u64,
+		options: StorageBindingOptions,
+	) -> Self {
 		let buffer = device.create_buffer(&wgpu::BufferDescriptor {
 			label: buffer_label.as_deref(),
-			usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-			size: max_elements * size_of::<T>() as u64,
+			usage: options.usage,
+			size: capacity * size_of::<T>() as u64,
 			mapped_at_creation: false,
 		});
 
@@ -30,9 +101,9 @@ impl<T> StorageBinding<T> {
 			label: bind_group_layout_label.as_deref(),
 			entries: &[wgpu::BindGroupLayoutEntry {
 				binding: 0,
-				visibility: wgpu::ShaderStages::VERTEX,
+				visibility: options.visibility,
 				ty: wgpu::BindingType::Buffer {
-					ty: wgpu::BufferBindingType::Storage { read_only: true },
+					ty: wgpu::BufferBindingType::Storage { read_only: options.read_only },
 					has_dynamic_offset: false,
 					min_binding_size: None,
 				},
@@ -47,7 +118,39 @@ impl<T> StorageBinding<T> {
 			layout: &bind_group_layout,
 		});
 
-		Self { buffer, bind_group_layout, bind_group, _marker: PhantomData }
+		Self { buffer, bind_group_layout, bind_group, label: buffer_label, capacity, usage: options.usage, _marker: PhantomData }
+	}
+
+	/// Upload `data` to the buffer, reallocating to the next power-of-two
+	/// capacity when it no longer fits. The [`BindGroupLayout`] is reused, but a
+	/// larger buffer requires a fresh [`BindGroup`]; the returned flag reports
+	/// whether the bind group was recreated so callers know to rebind.
+	pub fn write_slice(&mut self, device: &Device, queue: &Queue, data: &[T]) -> bool
+	where
+		T: bytemuck::Pod,
+	{
+		let needed = data.len() as u64;
+		let mut recreated = false;
+		if needed > self.capacity {
+			self.capacity = needed.next_power_of_two();
+			self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
+				label: self.label.as_deref(),
+				usage: self.usage,
+				size: self.capacity * size_of::<T>() as u64,
+				mapped_at_creation: false,
+			});
+
+			let bind_group_label = self.label.as_ref().map(|label| format!("{label} Bind Group"));
+			self.bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+				label: bind_group_label.as_deref(),
+				entries: &[wgpu::BindGroupEntry { binding: 0, resource: self.buffer.as_entire_binding() }],
+				layout: &self.bind_group_layout,
+			});
+			recreated = true;
+		}
+
+		queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(data));
+		recreated
 	}
 
 	pub fn buffer(&self) -> &Buffer {
@@ -124,14 +227,28 @@ impl<T> UniformBinding<T> {
 
 pub struct TextureBinding {
 	texture: Texture,
-	bind_group_layout: BindGroupLayout,
+	/// Optional second plane holding interleaved chroma for NV12 video sources.
+	/// `None` for ordinary textures, whose chroma binding aliases the primary
+	/// view and is ignored by the shader.
+	chroma: Option<Texture>,
+	bind_group_layout: Arc<BindGroupLayout>,
 	bind_group: BindGroup,
 }
 
 impl TextureBinding {
-	pub fn create(device: &Device, texture_label: Option<String>, texture: Texture) -> Self {
-		let bind_group_layout_label = texture_label.as_ref().map(|label| format!("{label} Bind Group Layout"));
-		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+	/// Build the bind group layout every [`TextureBinding`] is created against.
+	///
+	/// wgpu checks pipeline/bind-group-layout compatibility by object identity,
+	/// not structural equality, so every binding the sprite pipeline samples
+	/// from — the pool's default/white textures, a loaded path, or a streamed
+	/// [`VideoTexture`](crate::render::video::VideoTexture) — must be created
+	/// against this same layout object rather than each calling this
+	/// descriptor independently. Callers build it once (see
+	/// [`TexturePool::bind_group_layout`](crate::render::pool::TexturePool::bind_group_layout))
+	/// and pass it into every [`TextureBinding::create`]/[`TextureBinding::create_planar`] call.
+	pub fn create_layout(device: &Device) -> BindGroupLayout {
+		device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: Some("Texture Bind Group Layout"),
 			entries: &[
 				wgpu::BindGroupLayoutEntry {
 					binding: 0,
@@ -149,27 +266,72 @@ impl TextureBinding {
 					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
 					count: None,
 				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
 			],
-			label: bind_group_layout_label.as_deref(),
-		});
+		})
+	}
+
+	pub fn create(device: &Device, texture_label: Option<String>, layout: &Arc<BindGroupLayout>, texture: Texture) -> Self {
+		Self::create_inner(device, texture_label, layout, texture, None)
+	}
+
+	/// Create a binding over two planes: a luma plane in `texture` and an
+	/// interleaved chroma plane in `chroma`, as produced by an NV12 video
+	/// decoder. Both planes share the layout of an ordinary texture binding so
+	/// the same sprite pipeline can sample either.
+	pub fn create_planar(
+		device: &Device,
+		texture_label: Option<String>,
+		layout: &Arc<BindGroupLayout>,
+		texture: Texture,
+		chroma: Texture,
+	) -> Self {
+		Self::create_inner(device, texture_label, layout, texture, Some(chroma))
+	}
+
+	fn create_inner(
+		device: &Device,
+		texture_label: Option<String>,
+		layout: &Arc<BindGroupLayout>,
+		texture: Texture,
+		chroma: Option<Texture>,
+	) -> Self {
+		// Ordinary textures have no chroma plane; alias the primary view so the
+		// binding still satisfies the uniform layout.
+		let chroma_view = chroma.as_ref().map_or(&texture.view, |chroma| &chroma.view);
 
 		let bind_group_label = texture_label.as_ref().map(|label| format!("{label} Bind Group"));
 		let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-			layout: &bind_group_layout,
+			layout,
 			entries: &[
 				wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&texture.view) },
 				wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&texture.sampler) },
+				wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(chroma_view) },
 			],
 			label: bind_group_label.as_deref(),
 		});
 
-		Self { texture, bind_group_layout, bind_group }
+		Self { texture, chroma, bind_group_layout: Arc::clone(layout), bind_group }
 	}
 
 	pub fn texture(&self) -> &Texture {
 		&self.texture
 	}
 
+	/// The chroma plane of an NV12 binding, if any.
+	pub fn chroma(&self) -> Option<&Texture> {
+		self.chroma.as_ref()
+	}
+
 	pub fn bind_group_layout(&self) -> &BindGroupLayout {
 		&self.bind_group_layout
 	}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use wgpu::{BindGroupLayout, Device, Queue};
+
+use crate::render::sprite::SAMPLE_MODE_NV12;
+use crate::render::texture::Texture;
+use crate::render::util::TextureBinding;
+
+/// Pixel layout of the frames pushed into a [`VideoTexture`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VideoFormat {
+	/// Packed 8-bit RGBA, one plane.
+	Rgba,
+	/// NV12: a full-resolution luma plane and a half-resolution interleaved
+	/// Cb/Cr plane, converted to RGB in the sprite shader so the decoder does
+	/// not pay for CPU color conversion.
+	Nv12,
+}
+
+/// A sprite texture whose contents are replaced every frame from a
+/// caller-supplied decoded frame.
+///
+/// Each `push_frame_*` call streams new pixels into the existing GPU allocation
+/// via [`Queue::write_texture`]; the underlying [`wgpu::Texture`] is recreated
+/// only when the frame dimensions change. The binding matches the layout of an
+/// ordinary [`TextureBinding`], so a [`Sprite`](crate::render::Sprite) can point
+/// at a `VideoTexture` and the existing instanced pipeline renders the current
+/// frame. NV12 sources additionally set the sprite's sample mode to
+/// [`SAMPLE_MODE_NV12`](crate::render::sprite::SAMPLE_MODE_NV12).
+pub struct VideoTexture {
+	format: VideoFormat,
+	width: u32,
+	height: u32,
+	binding: Arc<TextureBinding>,
+	label: Option<String>,
+	/// The pool's shared texture bind group layout, kept so a resize can
+	/// reallocate the binding against the same layout object. See
+	/// [`TextureBinding::create_layout`].
+	bind_group_layout: Arc<BindGroupLayout>,
+}
+
+impl VideoTexture {
+	/// `bind_group_layout` must be the layout the destination
+	/// [`TexturePool`](crate::render::pool::TexturePool) was built with — see
+	/// [`TexturePool::bind_group_layout`](crate::render::pool::TexturePool::bind_group_layout)
+	/// — so the binding stays compatible with the sprite pipeline once
+	/// registered via
+	/// [`TexturePool::register_binding`](crate::render::pool::TexturePool::register_binding).
+	pub fn new(
+		device: &Device,
+		bind_group_layout: &Arc<BindGroupLayout>,
+		format: VideoFormat,
+		width: u32,
+		height: u32,
+		label: Option<String>,
+	) -> Self {
+		let binding = Self::allocate(device, bind_group_layout, format, width, height, &label);
+		Self { format, width, height, binding, label, bind_group_layout: Arc::clone(bind_group_layout) }
+	}
+
+	/// Upload a packed RGBA frame, reallocating the texture if the dimensions
+	/// changed. Expects `4 * width * height` bytes.
+	pub fn push_frame_rgba(&mut self, device: &Device, queue: &Queue, width: u32, height: u32, rgba: &[u8]) {
+		debug_assert_eq!(self.format, VideoFormat::Rgba, "push_frame_rgba on a non-RGBA video texture");
+		self.reallocate_if_resized(device, width, height);
+		Self::write_plane(queue, self.binding.texture().raw(), width, height, 4, rgba);
+	}
+
+	/// Upload an NV12 frame as separate luma and interleaved-chroma planes,
+	/// reallocating if the dimensions changed. The chroma plane is half-size in
+	/// each dimension with two interleaved bytes per texel.
+	pub fn push_frame_nv12(
+		&mut self,
+		device: &Device,
+		queue: &Queue,
+		width: u32,
+		height: u32,
+		luma: &[u8],
+		chroma: &[u8],
+	) {
+		debug_assert_eq!(self.format, VideoFormat::Nv12, "push_frame_nv12 on a non-NV12 video texture");
+		self.reallocate_if_resized(device, width, height);
+		Self::write_plane(queue, self.binding.texture().raw(), width, height, 1, luma);
+
+		let chroma_texture = self.binding.chroma().expect("NV12 video texture is missing its chroma plane");
+		Self::write_plane(queue, chroma_texture.raw(), width / 2, height / 2, 2, chroma);
+	}
+
+	/// The texture binding carrying the current frame, ready to be bound by the
+	/// sprite pipeline.
+	pub fn binding(&self) -> &TextureBinding {
+		&self.binding
+	}
+
+	/// A shared handle to the current frame's binding, for registering the video
+	/// source with the [texture pool](crate::render::pool::TexturePool). Frames
+	/// pushed at a constant resolution update the shared texture in place, so a
+	/// sprite holding the returned handle renders the latest frame; re-register
+	/// after a resolution change, which allocates a fresh binding.
+	pub fn binding_arc(&self) -> Arc<TextureBinding> {
+		self.binding.clone()
+	}
+
+	/// The sample mode a sprite must use to render this source correctly.
+	pub fn sample_mode(&self) -> u32 {
+		match self.format {
+			VideoFormat::Rgba => crate::render::sprite::SAMPLE_MODE_RGBA,
+			VideoFormat::Nv12 => SAMPLE_MODE_NV12,
+		}
+	}
+
+	fn reallocate_if_resized(&mut self, device: &Device, width: u32, height: u32) {
+		if width != self.width || height != self.height {
+			self.width = width;
+			self.height = height;
+			self.binding = Self::allocate(device, &self.bind_group_layout, self.format, width, height, &self.label);
+		}
+	}
+
+	fn allocate(
+		device: &Device,
+		bind_group_layout: &Arc<BindGroupLayout>,
+		format: VideoFormat,
+		width: u32,
+		height: u32,
+		label: &Option<String>,
+	) -> Arc<TextureBinding> {
+		let binding = match format {
+			VideoFormat::Rgba => {
+				let texture = Texture::empty(device, width, height, wgpu::TextureFormat::Rgba8UnormSrgb);
+				TextureBinding::create(device, label.clone(), bind_group_layout, texture)
+			}
+			VideoFormat::Nv12 => {
+				let luma = Texture::empty(device, width, height, wgpu::TextureFormat::R8Unorm);
+				let chroma = Texture::empty(device, width / 2, height / 2, wgpu::TextureFormat::Rg8Unorm);
+				TextureBinding::create_planar(device, label.clone(), bind_group_layout, luma, chroma)
+			}
+		};
+
+		Arc::new(binding)
+	}
+
+	fn write_plane(queue: &Queue, texture: &wgpu::Texture, width: u32, height: u32, bytes_per_texel: u32, data: &[u8]) {
+		queue.write_texture(
+			wgpu::TexelCopyTextureInfo {
+				texture,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+				mip_level: 0,
+			},
+			data,
+			wgpu::TexelCopyBufferLayout {
+				offset: 0,
+				bytes_per_row: Some(bytes_per_texel * width),
+				rows_per_image: Some(height),
+			},
+			wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+		);
+	}
+}
@@ -7,20 +7,35 @@ pub struct Transform {
 	pub position: Vec2,
 	pub scale: Vec2,
 	pub rotation: f32,
+	/// Depth layer used to order sprites front-to-back. Lower values are closer
+	/// to the camera and occlude higher ones through the depth buffer.
+	pub layer: f32,
 }
 
 impl Transform {
 	pub fn new(position: Vec2, rotation: f32, scale: Vec2) -> Self {
-		Self { position, rotation, scale }
+		Self { position, rotation, scale, ..Default::default() }
 	}
 
 	pub fn from_translation(x: f32, y: f32) -> Self {
 		Self { position: Vec2::new(x, y), ..Default::default() }
 	}
 
-	/// Convert the transform component into a 4x4 matrix.
+	/// Set the depth layer used for sprite ordering.
+	///
+	/// Valid layers lie in `[-DEPTH_RANGE, DEPTH_RANGE]`
+	/// (see [`DEPTH_RANGE`](crate::render::camera::DEPTH_RANGE)); a sprite placed
+	/// outside that interval is clipped by the orthographic near/far planes and
+	/// does not render.
+	pub fn with_layer(mut self, layer: f32) -> Self {
+		self.layer = layer;
+		self
+	}
+
+	/// Convert the transform component into a 4x4 matrix. The layer is written
+	/// into the Z translation so the depth buffer can resolve occlusion.
 	pub fn matrix(&self) -> Mat4 {
-		let translation = Mat4::from_translation(self.position.extend(0.0));
+		let translation = Mat4::from_translation(self.position.extend(self.layer));
 		let rotation = Mat4::from_rotation_z(self.rotation);
 		let scale = Mat4::from_scale(self.scale.extend(1.0));
 
@@ -30,7 +45,7 @@ impl Transform {
 
 impl Default for Transform {
 	fn default() -> Self {
-		Self { position: Vec2::ZERO, scale: Vec2::splat(32.0), rotation: 0.0 }
+		Self { position: Vec2::ZERO, scale: Vec2::splat(32.0), rotation: 0.0, layer: 0.0 }
 	}
 }
 